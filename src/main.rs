@@ -1,5 +1,6 @@
 use core::fmt;
 use std::{
+    collections::{HashMap, HashSet, VecDeque},
     env,
     ffi::CStr,
     fmt::write,
@@ -14,7 +15,8 @@ use clap::{Parser, Subcommand};
 use flate2::Compression;
 use sha1::{Digest, Sha1};
 
-const GIT_OBJECT_PATH: &'static str = ".git/objects";
+const GIT_OBJECT_PATH: &str = ".git/objects";
+const GIT_CONFIG_PATH: &str = ".git/config";
 const NODE_HASH_BYTES_LENGTH: usize = 20;
 
 #[derive(Debug, Subcommand)]
@@ -46,6 +48,32 @@ enum Commands {
         #[arg(short = 'm')]
         message: String,
     },
+    /// clone a remote repository over the git smart HTTP protocol
+    Clone {
+        url: String,
+        directory: Option<String>,
+    },
+    /// fetch objects and refs from a remote into the current repository
+    Fetch {
+        url: String,
+    },
+    /// show a unified diff between two blob or tree objects
+    Diff {
+        old: String,
+        new: String,
+    },
+    /// export a tree or commit as a tar (or tar.gz) archive
+    Archive {
+        tree_or_commit: String,
+        /// write the archive to a file instead of stdout; a `.gz`/`.tgz` extension gzips it
+        #[arg(short = 'o')]
+        output: Option<String>,
+    },
+    /// walk commit ancestry, printing each commit in git-log format
+    Log {
+        /// commit to start from; defaults to HEAD
+        commit_sha: Option<String>,
+    },
 }
 
 #[derive(Debug, Parser)]
@@ -58,10 +86,10 @@ struct Cli {
 
 fn get_path_from_hash(object_hash: &str) -> (String, String) {
     const DIRECTORY_LENGTH: usize = 2;
-    return (
+    (
         object_hash[..DIRECTORY_LENGTH].to_string(),
         object_hash[DIRECTORY_LENGTH..].to_string(),
-    );
+    )
 }
 
 fn get_object_path(dir_path: &str, hash_path: &str) -> Result<String> {
@@ -89,29 +117,237 @@ fn get_object_path(dir_path: &str, hash_path: &str) -> Result<String> {
         ));
     }
 
-    return Ok(format!("{}/{}", full_dir_path, possible_hash_path[0]));
+    Ok(format!("{}/{}", full_dir_path, possible_hash_path[0]))
+}
+
+// --- object id ----------------------------------------------------------
+
+/// A validated, fully-resolved 20-byte SHA-1 object id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Oid([u8; NODE_HASH_BYTES_LENGTH]);
+
+impl Oid {
+    fn from_bytes(bytes: [u8; NODE_HASH_BYTES_LENGTH]) -> Self {
+        Oid(bytes)
+    }
+}
+
+impl fmt::Display for Oid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum OidParseError {
+    InvalidLength(usize),
+    InvalidHex { octet: String, position: usize },
+}
+
+impl fmt::Display for OidParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OidParseError::InvalidLength(len) => write!(
+                f,
+                "invalid object id: expected 40 hex characters, got {}",
+                len
+            ),
+            OidParseError::InvalidHex { octet, position } => write!(
+                f,
+                "invalid object id: octet '{}' at position {} is not valid hex",
+                octet, position
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OidParseError {}
+
+impl std::str::FromStr for Oid {
+    type Err = OidParseError;
+
+    fn from_str(hash: &str) -> Result<Self, Self::Err> {
+        if hash.len() != NODE_HASH_BYTES_LENGTH * 2 {
+            return Err(OidParseError::InvalidLength(hash.len()));
+        }
+
+        let mut bytes = [0u8; NODE_HASH_BYTES_LENGTH];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            let octet = &hash[i * 2..i * 2 + 2];
+            *byte = u8::from_str_radix(octet, 16).map_err(|_| OidParseError::InvalidHex {
+                octet: octet.to_string(),
+                position: i,
+            })?;
+        }
+        Ok(Oid(bytes))
+    }
+}
+
+fn object_path_for(oid: &Oid) -> String {
+    let hex = oid.to_string();
+    format!("{}/{}/{}", GIT_OBJECT_PATH, &hex[..2], &hex[2..])
+}
+
+// resolves a (possibly abbreviated, minimum 4 characters) hash into a full
+// `Oid`, scanning the matching `.git/objects/<dir>` bucket and erroring
+// clearly on ambiguity or no-match, same as real git's short-SHA lookup.
+fn resolve_oid(hash_prefix: &str) -> Result<Oid> {
+    if hash_prefix.len() < 4 {
+        bail!(
+            "hash prefix '{}' is too short to resolve (minimum 4 characters)",
+            hash_prefix
+        );
+    }
+    if hash_prefix.len() == NODE_HASH_BYTES_LENGTH * 2 {
+        let oid = hash_prefix.parse::<Oid>()?;
+        if !std::path::Path::new(&object_path_for(&oid)).is_file() {
+            bail!("no matching path for hash: {}", hash_prefix);
+        }
+        return Ok(oid);
+    }
+
+    let (dir_path, hash_path) = get_path_from_hash(hash_prefix);
+    let full_path = get_object_path(&dir_path, &hash_path)?;
+    let file_name = full_path
+        .rsplit('/')
+        .next()
+        .with_context(|| "malformed object path")?;
+    Ok(format!("{}{}", dir_path, file_name).parse::<Oid>()?)
 }
 
-// TODO: get the actual author from git config file
-fn get_commit_author_name() -> String {
-    "test_author".to_string()
+// --- git config -------------------------------------------------------
+//
+// A small INI reader for `.git/config`-style files: `[section]` headers,
+// `key = value` pairs (quoted values and `#`/`;` comments are tolerated),
+// merged across `.git/config`, the user config, and the system config in
+// that precedence order (`.git/config` wins).
+
+type GitConfig = HashMap<String, HashMap<String, String>>;
+
+fn strip_config_comment(line: &str) -> &str {
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '#' | ';' if !in_quotes => return &line[..i],
+            _ => {}
+        }
+    }
+    line
 }
 
-fn get_commit_author_email() -> String {
-    "test_author@gmail.com".to_string()
+fn unquote_config_value(value: &str) -> String {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
 }
 
-fn get_commit_comitter_email() -> String {
-    "test_comitter@gmail.com".to_string()
+fn parse_git_config_str(content: &str) -> GitConfig {
+    let mut sections: GitConfig = HashMap::new();
+    let mut current_section = String::new();
+
+    for raw_line in content.lines() {
+        let line = strip_config_comment(raw_line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            current_section = line[1..line.len() - 1].trim().to_lowercase();
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            sections
+                .entry(current_section.clone())
+                .or_default()
+                .insert(key.trim().to_lowercase(), unquote_config_value(value.trim()));
+        }
+    }
+    sections
 }
 
-fn get_commit_comitter_name() -> String {
-    "test_comitter".to_string()
+fn global_git_config_path() -> Option<std::path::PathBuf> {
+    if let Ok(xdg) = env::var("XDG_CONFIG_HOME") {
+        let path = std::path::PathBuf::from(xdg).join("git/config");
+        if path.exists() {
+            return Some(path);
+        }
+    }
+    Some(std::path::PathBuf::from(env::var("HOME").ok()?).join(".gitconfig"))
+}
+
+// loads and merges `.git/config`, the global config, and `/etc/gitconfig`,
+// most-specific last so `.git/config` wins ties.
+fn load_git_config() -> GitConfig {
+    let mut merged: GitConfig = HashMap::new();
+    let mut apply_layer = |path: std::path::PathBuf| {
+        let Ok(content) = fs::read_to_string(&path) else {
+            return;
+        };
+        for (section, entries) in parse_git_config_str(&content) {
+            let target = merged.entry(section).or_default();
+            target.extend(entries);
+        }
+    };
+
+    apply_layer(std::path::PathBuf::from("/etc/gitconfig"));
+    if let Some(global_path) = global_git_config_path() {
+        apply_layer(global_path);
+    }
+    apply_layer(std::path::PathBuf::from(GIT_CONFIG_PATH));
+
+    merged
+}
+
+fn config_get(config: &GitConfig, section: &str, key: &str) -> Option<String> {
+    config.get(section)?.get(key).cloned()
+}
+
+// resolves author/committer name and email, environment variables taking
+// precedence over `user.name`/`user.email` from config, and fails with a
+// helpful message when no identity is configured at all (matching how real
+// git refuses to create a commit without one).
+fn resolve_identity(
+    config: &GitConfig,
+    name_env: &str,
+    email_env: &str,
+) -> Result<(String, String)> {
+    let name = env::var(name_env)
+        .ok()
+        .or_else(|| config_get(config, "user", "name"))
+        .with_context(|| {
+            format!(
+                "no identity configured: set user.name in .git/config or ~/.gitconfig, or the {} environment variable",
+                name_env
+            )
+        })?;
+    let email = env::var(email_env)
+        .ok()
+        .or_else(|| config_get(config, "user", "email"))
+        .with_context(|| {
+            format!(
+                "no identity configured: set user.email in .git/config or ~/.gitconfig, or the {} environment variable",
+                email_env
+            )
+        })?;
+    Ok((name, email))
+}
+
+fn resolve_timestamp(date_env: &str) -> Result<chrono::DateTime<FixedOffset>> {
+    match env::var(date_env) {
+        Ok(value) => chrono::DateTime::parse_from_str(&value, "%s %z")
+            .with_context(|| format!("failed to parse {}: {}", date_env, value)),
+        Err(_) => Ok(Utc::now().fixed_offset()),
+    }
 }
 
 struct TreeNode {
     name: String,
-    hash: String,
+    hash: Oid,
     mode: u32,
 }
 
@@ -129,11 +365,7 @@ impl TreeNode {
         let mut bytes = Vec::new();
         let _ =
             write!(bytes, "{} {}\0", self.mode, self.name).context("writing to Vec is infallible");
-        let hash_bytes: Vec<u8> = (0..self.hash.len())
-            .step_by(2)
-            .map(|i| u8::from_str_radix(&self.hash[i..i + 2], 16).expect("invalid hex in hash"))
-            .collect();
-        bytes.extend_from_slice(&hash_bytes);
+        bytes.extend_from_slice(&self.hash.0);
         Ok(bytes)
     }
 }
@@ -153,34 +385,35 @@ impl fmt::Display for TreeNode {
 
 struct CommitContent {
     tree_sha: String,
-    parent_sha: Option<String>,
+    parent_shas: Vec<String>,
     author_name: String,
     author_email: String,
     committer: String,
     committer_email: String,
     message: String,
-    timestamp: chrono::DateTime<FixedOffset>,
+    author_timestamp: chrono::DateTime<FixedOffset>,
+    committer_timestamp: chrono::DateTime<FixedOffset>,
 }
 
 impl fmt::Display for CommitContent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let _ = writeln!(f, "tree {}", self.tree_sha);
-        if let Some(sha) = &self.parent_sha {
+        for sha in &self.parent_shas {
             let _ = writeln!(f, "parent {}", sha);
         }
         let _ = writeln!(
             f,
-            "author {} {} {}",
+            "author {} <{}> {}",
             self.author_name,
             self.author_email,
-            self.timestamp.format("%s %z").to_string()
+            self.author_timestamp.format("%s %z")
         );
         let _ = writeln!(
             f,
-            "committer {} {} {}",
+            "committer {} <{}> {}",
             self.committer,
             self.committer_email,
-            self.timestamp.format("%s %z").to_string()
+            self.committer_timestamp.format("%s %z")
         );
 
         write!(f, "\n{}", self.message)
@@ -188,7 +421,7 @@ impl fmt::Display for CommitContent {
 }
 
 enum ObjectHashTypes {
-    Blob(String),
+    Blob(Vec<u8>),
     Tree(Vec<TreeNode>), // fill in as needed
     Commit(CommitContent),
 }
@@ -196,7 +429,10 @@ enum ObjectHashTypes {
 impl fmt::Display for ObjectHashTypes {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ObjectHashTypes::Blob(content) => write!(f, "{}", content),
+            ObjectHashTypes::Blob(content) => match std::str::from_utf8(content) {
+                Ok(text) => write!(f, "{}", text),
+                Err(_) => write!(f, "binary file, {} bytes", content.len()),
+            },
             ObjectHashTypes::Tree(items) => {
                 for tree_node in items {
                     writeln!(f, "{}", tree_node)?;
@@ -204,7 +440,7 @@ impl fmt::Display for ObjectHashTypes {
                 Ok(())
             }
             ObjectHashTypes::Commit(commit_content) => {
-                return write!(f, "{}", commit_content);
+                write!(f, "{}", commit_content)
             }
         }
     }
@@ -221,11 +457,11 @@ fn parse_file_metadata(meta_data: &str) -> Result<(&str, &str)> {
             .with_context(|| format!("no size found on metadata {}", &meta_data))?,
     );
 
-    return Ok((first_data, second_data));
+    Ok((first_data, second_data))
 }
 
 fn parse_object_hash(hash_object: &str) -> Result<ObjectHashTypes> {
-    let (dir_path, object_path) = get_path_from_hash(&hash_object);
+    let (dir_path, object_path) = get_path_from_hash(hash_object);
     let object_path = get_object_path(&dir_path, &object_path)?;
     let file =
         File::open(&object_path).context(format!("failed to read object path {}", &object_path))?;
@@ -254,9 +490,7 @@ fn parse_object_hash(hash_object: &str) -> Result<ObjectHashTypes> {
     let _ = buf_reader.read_exact(&mut buffer);
 
     match content_type {
-        "blob" => Ok(ObjectHashTypes::Blob(
-            String::from_utf8(buffer).context("parsing buffer to string utf-8")?,
-        )),
+        "blob" => Ok(ObjectHashTypes::Blob(buffer)),
         "tree" => {
             let (mut position, mut tree_nodes) = (0, Vec::new());
             while position < buffer.len() {
@@ -278,10 +512,10 @@ fn parse_object_hash(hash_object: &str) -> Result<ObjectHashTypes> {
                     .with_context(|| format!("failed to parse mode, found {}", mode))?;
 
                 let sha_start = metadata_end + 1;
-                let node_hash = buffer[sha_start..sha_start + NODE_HASH_BYTES_LENGTH]
-                    .iter()
-                    .map(|b| format!("{:02x}", b))
-                    .collect::<String>();
+                let mut node_hash_bytes = [0u8; NODE_HASH_BYTES_LENGTH];
+                node_hash_bytes
+                    .copy_from_slice(&buffer[sha_start..sha_start + NODE_HASH_BYTES_LENGTH]);
+                let node_hash = Oid::from_bytes(node_hash_bytes);
 
                 tree_nodes.push(TreeNode {
                     name: name.to_string(),
@@ -316,20 +550,23 @@ fn parse_object_hash(hash_object: &str) -> Result<ObjectHashTypes> {
 
             const COMMIT_FIELDS: [&str; 4] = ["tree", "parent", "author", "committer"];
 
-            let find_field = |prefix: &str| -> Option<String> {
+            let find_fields = |prefix: &str| -> Vec<String> {
                 header_lines
                     .iter()
                     .filter_map(|l| l.as_deref())
-                    .find(|line| {
+                    .filter(|line| {
                         line.starts_with(prefix)
                             && line.as_bytes().get(prefix.len()) == Some(&b' ')
                     })
                     .map(|line| line[prefix.len() + 1..].to_string())
+                    .collect()
             };
 
+            let find_field = |prefix: &str| -> Option<String> { find_fields(prefix).into_iter().next() };
+
             let tree_sha = find_field(COMMIT_FIELDS[0])
                 .with_context(|| "failed to find tree in commit")?;
-            let parent_sha = find_field(COMMIT_FIELDS[1]);
+            let parent_shas = find_fields(COMMIT_FIELDS[1]);
             let author_line = find_field(COMMIT_FIELDS[2])
                 .with_context(|| "failed to find author in commit")?;
             let committer_line = find_field(COMMIT_FIELDS[3])
@@ -341,6 +578,8 @@ fn parse_object_hash(hash_object: &str) -> Result<ObjectHashTypes> {
             let author_email = author_parts
                 .get(n.saturating_sub(3))
                 .with_context(|| format!("failed to parse email from author: {}", author_line))?
+                .trim_start_matches('<')
+                .trim_end_matches('>')
                 .to_string();
             let unix_ts = author_parts
                 .get(n.saturating_sub(2))
@@ -348,7 +587,7 @@ fn parse_object_hash(hash_object: &str) -> Result<ObjectHashTypes> {
             let tz = author_parts
                 .get(n.saturating_sub(1))
                 .with_context(|| "missing timezone in author")?;
-            let timestamp =
+            let author_timestamp =
                 chrono::DateTime::parse_from_str(&format!("{} {}", unix_ts, tz), "%s %z")
                     .with_context(|| format!("failed to parse timestamp: {} {}", unix_ts, tz))?;
 
@@ -360,36 +599,55 @@ fn parse_object_hash(hash_object: &str) -> Result<ObjectHashTypes> {
                 .with_context(|| {
                     format!("failed to parse email from committer: {}", committer_line)
                 })?
+                .trim_start_matches('<')
+                .trim_end_matches('>')
                 .to_string();
+            let committer_unix_ts = committer_parts
+                .get(m.saturating_sub(2))
+                .with_context(|| "missing unix timestamp in committer")?;
+            let committer_tz = committer_parts
+                .get(m.saturating_sub(1))
+                .with_context(|| "missing timezone in committer")?;
+            let committer_timestamp = chrono::DateTime::parse_from_str(
+                &format!("{} {}", committer_unix_ts, committer_tz),
+                "%s %z",
+            )
+            .with_context(|| {
+                format!(
+                    "failed to parse timestamp: {} {}",
+                    committer_unix_ts, committer_tz
+                )
+            })?;
 
             Ok(ObjectHashTypes::Commit(CommitContent {
                 tree_sha,
-                parent_sha,
+                parent_shas,
                 author_name,
                 author_email,
                 committer,
                 committer_email,
                 message,
-                timestamp,
+                author_timestamp,
+                committer_timestamp,
             }))
         }
         _ => bail!(format!("unsupported type: {}", content_type)),
     }
 }
 
-fn write_object(meta_data: &[u8], content: &[u8]) -> Result<String> {
+fn write_object(meta_data: &[u8], content: &[u8]) -> Result<Oid> {
     let mut hasher = Sha1::new();
 
-    hasher.update(&meta_data);
-    hasher.update(&content);
+    hasher.update(meta_data);
+    hasher.update(content);
 
-    let hash_object = format!("{:x}", hasher.finalize());
-    let (dir_path, hash) = get_path_from_hash(&hash_object);
-    let full_dir_path = format!("{}/{}", GIT_OBJECT_PATH, dir_path);
-    let full_path = format!("{}/{}", full_dir_path, hash);
+    let digest: [u8; NODE_HASH_BYTES_LENGTH] = hasher.finalize().into();
+    let oid = Oid::from_bytes(digest);
+    let full_dir_path = format!("{}/{}", GIT_OBJECT_PATH, &oid.to_string()[..2]);
+    let full_path = object_path_for(&oid);
 
     if std::path::Path::new(&full_path).exists() {
-        return Ok(hash_object);
+        return Ok(oid);
     }
 
     fs::create_dir_all(&full_dir_path)
@@ -407,14 +665,14 @@ fn write_object(meta_data: &[u8], content: &[u8]) -> Result<String> {
     let _ = zlib_encoder.write(meta_data);
     let _ = zlib_encoder.write(content);
 
-    Ok(hash_object)
+    Ok(oid)
 }
 
-fn write_object_hash(object_hash_type: ObjectHashTypes) -> Result<String> {
+fn write_object_hash(object_hash_type: ObjectHashTypes) -> Result<Oid> {
     let (write_metadata, write_content) = match object_hash_type {
         ObjectHashTypes::Blob(content) => {
             let meta_data = format!("blob {}\0", content.len());
-            (meta_data.as_bytes().to_vec(), content.as_bytes().to_vec())
+            (meta_data.as_bytes().to_vec(), content)
         }
         ObjectHashTypes::Tree(items) => {
             let mut content = Vec::new();
@@ -435,7 +693,7 @@ fn write_object_hash(object_hash_type: ObjectHashTypes) -> Result<String> {
         }
     };
 
-    return write_object(&write_metadata, &write_content);
+    write_object(&write_metadata, &write_content)
 }
 
 //@Performance: this is really slow, imagine hashing the whole content again and again
@@ -448,10 +706,8 @@ fn get_tree_nodes_from_git_directory(path: &std::path::Path) -> Result<Vec<TreeN
         let metadata = entry.metadata()?;
 
         if metadata.is_file() {
-            let content = String::from_utf8(
-                fs::read(entry.path()).context(format!("failed reading {:?}", &entry.path()))?,
-            )
-            .context(format!("failed parsing {:?} to string", entry.path()))?;
+            let content =
+                fs::read(entry.path()).context(format!("failed reading {:?}", &entry.path()))?;
             let hash_object = write_object_hash(ObjectHashTypes::Blob(content))?;
             let git_mode = if metadata.mode() & 0o111 != 0 {
                 100755
@@ -478,7 +734,855 @@ fn get_tree_nodes_from_git_directory(path: &std::path::Path) -> Result<Vec<TreeN
     }
 
     tree_nodes.sort_by(|a, b| a.name.cmp(&b.name));
-    return Ok(tree_nodes);
+    Ok(tree_nodes)
+}
+
+// --- pkt-line codec -------------------------------------------------------
+//
+// The git smart protocol frames every message as a pkt-line: a 4-byte ASCII
+// hex length prefix (counting the 4 prefix bytes themselves) followed by
+// that many payload bytes. `0000` is a flush-pkt and `0001` is a delim-pkt,
+// both of which carry no payload.
+
+#[derive(Debug, PartialEq)]
+enum PktLine {
+    Flush,
+    Delimiter,
+    Data(Vec<u8>),
+}
+
+fn encode_pkt_line(payload: &[u8]) -> Vec<u8> {
+    let mut out = format!("{:04x}", payload.len() + 4).into_bytes();
+    out.extend_from_slice(payload);
+    out
+}
+
+const FLUSH_PKT: &[u8] = b"0000";
+const DELIM_PKT: &[u8] = b"0001";
+
+fn read_pkt_line(data: &[u8], pos: &mut usize) -> Result<PktLine> {
+    let len_bytes = data
+        .get(*pos..*pos + 4)
+        .with_context(|| "truncated pkt-line length prefix")?;
+    let len = usize::from_str_radix(std::str::from_utf8(len_bytes)?, 16)
+        .with_context(|| "invalid pkt-line length prefix")?;
+    *pos += 4;
+
+    match len {
+        0 => Ok(PktLine::Flush),
+        1 => Ok(PktLine::Delimiter),
+        _ => {
+            let payload = data
+                .get(*pos..*pos + (len - 4))
+                .with_context(|| "truncated pkt-line payload")?
+                .to_vec();
+            *pos += len - 4;
+            Ok(PktLine::Data(payload))
+        }
+    }
+}
+
+// --- smart HTTP v2 transport ----------------------------------------------
+
+fn discover_refs(client: &reqwest::blocking::Client, url: &str) -> Result<()> {
+    let response = client
+        .get(format!("{}/info/refs?service=git-upload-pack", url))
+        .header("Git-Protocol", "version=2")
+        .send()
+        .with_context(|| format!("failed to reach {}", url))?
+        .error_for_status()
+        .with_context(|| format!("{} did not respond to the service discovery request", url))?;
+    // we only need to make sure the remote understands protocol v2; the
+    // body is a handful of pkt-lines advertising capabilities which we do
+    // not need to inspect any further.
+    let _ = response.bytes()?;
+    Ok(())
+}
+
+/// ref name paired with the SHA it points to, as advertised by `ls-refs`.
+type RemoteRefs = Vec<(String, String)>;
+
+fn ls_refs(client: &reqwest::blocking::Client, url: &str) -> Result<RemoteRefs> {
+    let mut body = Vec::new();
+    body.extend(encode_pkt_line(b"command=ls-refs\n"));
+    body.extend(DELIM_PKT);
+    body.extend(encode_pkt_line(b"peel\n"));
+    body.extend(encode_pkt_line(b"ref-prefix HEAD\n"));
+    body.extend(encode_pkt_line(b"ref-prefix refs/heads/\n"));
+    body.extend(FLUSH_PKT);
+
+    let response = client
+        .post(format!("{}/git-upload-pack", url))
+        .header("Content-Type", "application/x-git-upload-pack-request")
+        .header("Git-Protocol", "version=2")
+        .body(body)
+        .send()
+        .with_context(|| format!("failed to ls-refs from {}", url))?
+        .error_for_status()?
+        .bytes()?;
+
+    let mut refs = Vec::new();
+    let mut pos = 0;
+    while pos < response.len() {
+        match read_pkt_line(&response, &mut pos)? {
+            PktLine::Flush | PktLine::Delimiter => break,
+            PktLine::Data(line) => {
+                let line = String::from_utf8_lossy(&line);
+                let mut parts = line.trim_end().splitn(2, ' ');
+                let sha = parts.next().with_context(|| "malformed ls-refs line")?;
+                let name = parts.next().with_context(|| "malformed ls-refs line")?;
+                refs.push((name.to_string(), sha.to_string()));
+            }
+        }
+    }
+    Ok(refs)
+}
+
+fn fetch_pack(client: &reqwest::blocking::Client, url: &str, wants: &[String]) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    body.extend(encode_pkt_line(b"command=fetch\n"));
+    body.extend(DELIM_PKT);
+    for want in wants {
+        body.extend(encode_pkt_line(format!("want {}\n", want).as_bytes()));
+    }
+    body.extend(encode_pkt_line(b"done\n"));
+    body.extend(FLUSH_PKT);
+
+    let response = client
+        .post(format!("{}/git-upload-pack", url))
+        .header("Content-Type", "application/x-git-upload-pack-request")
+        .header("Git-Protocol", "version=2")
+        .body(body)
+        .send()
+        .with_context(|| format!("failed to fetch pack from {}", url))?
+        .error_for_status()?
+        .bytes()?;
+
+    // the fetch response is a sequence of pkt-lines, the "packfile" section
+    // of which carries band-1 ("pack data") prefixed payloads; everything
+    // else (progress on band 2, errors on band 3) is discarded.
+    let mut pack = Vec::new();
+    let mut pos = 0;
+    let mut in_packfile_section = false;
+    while pos < response.len() {
+        match read_pkt_line(&response, &mut pos)? {
+            PktLine::Flush => break,
+            PktLine::Delimiter => continue,
+            PktLine::Data(line) => {
+                if !in_packfile_section {
+                    if line == b"packfile\n" {
+                        in_packfile_section = true;
+                    }
+                    continue;
+                }
+                if let Some((&1, payload)) = line.split_first() {
+                    pack.extend_from_slice(payload);
+                }
+            }
+        }
+    }
+    Ok(pack)
+}
+
+// --- PACK file reader -------------------------------------------------------
+//
+// A packfile is a "PACK" magic, a 4-byte big-endian version, a 4-byte
+// big-endian object count, that many packed objects, and a trailing 20-byte
+// SHA-1 of everything before it.
+
+const PACK_OBJ_COMMIT: u8 = 1;
+const PACK_OBJ_TREE: u8 = 2;
+const PACK_OBJ_BLOB: u8 = 3;
+const PACK_OBJ_TAG: u8 = 4;
+const PACK_OBJ_OFS_DELTA: u8 = 6;
+const PACK_OBJ_REF_DELTA: u8 = 7;
+
+fn pack_obj_type_name(obj_type: u8) -> Result<&'static str> {
+    match obj_type {
+        PACK_OBJ_COMMIT => Ok("commit"),
+        PACK_OBJ_TREE => Ok("tree"),
+        PACK_OBJ_BLOB => Ok("blob"),
+        PACK_OBJ_TAG => Ok("tag"),
+        _ => bail!("object type {} has no plain-text name", obj_type),
+    }
+}
+
+// reads the variable-length (type, size) object header: the first byte
+// holds a 3-bit type in bits 4-6 and the low 4 size bits, its MSB is a
+// continuation flag, and every following byte contributes 7 more size bits
+// little-endian until a byte with the MSB unset is read.
+fn read_pack_object_header(data: &[u8], pos: &mut usize) -> Result<(u8, u64)> {
+    let mut byte = *data.get(*pos).with_context(|| "truncated pack object header")?;
+    *pos += 1;
+
+    let obj_type = (byte >> 4) & 0b111;
+    let mut size = (byte & 0x0f) as u64;
+    let mut shift = 4;
+    while byte & 0x80 != 0 {
+        byte = *data.get(*pos).with_context(|| "truncated pack object header")?;
+        *pos += 1;
+        size |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+    }
+    Ok((obj_type, size))
+}
+
+// the ofs-delta base offset uses git's own big-endian-ish varint: unlike
+// the size varint above, each continuation byte shifts in 7 more low bits
+// *and* adds one, so that every encoding of a given offset is unique.
+fn read_ofs_delta_offset(data: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut byte = *data.get(*pos).with_context(|| "truncated ofs-delta offset")?;
+    *pos += 1;
+    let mut value = (byte & 0x7f) as u64;
+    while byte & 0x80 != 0 {
+        byte = *data.get(*pos).with_context(|| "truncated ofs-delta offset")?;
+        *pos += 1;
+        value = ((value + 1) << 7) | (byte & 0x7f) as u64;
+    }
+    Ok(value)
+}
+
+// zlib-inflates a single object's worth of data starting at `pos`, given the
+// expected inflated size, returning the inflated bytes and advancing `pos`
+// past exactly the compressed bytes it consumed (objects are packed back to
+// back with no length prefix, so we rely on `Decompress::total_in`).
+fn inflate_one(data: &[u8], pos: &mut usize, expected_size: u64) -> Result<Vec<u8>> {
+    let mut decompress = flate2::Decompress::new(true);
+    let mut out = vec![0u8; expected_size as usize];
+    decompress.decompress(
+        &data[*pos..],
+        &mut out,
+        flate2::FlushDecompress::Finish,
+    )?;
+    *pos += decompress.total_in() as usize;
+    Ok(out)
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*pos).with_context(|| "truncated delta varint")?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    Ok(value)
+}
+
+// applies a copy/insert delta instruction stream against `base`, producing
+// the reconstructed object bytes.
+fn apply_delta(base: &[u8], delta: &[u8]) -> Result<Vec<u8>> {
+    let mut pos = 0;
+    let base_size = read_varint(delta, &mut pos)?;
+    if base_size as usize != base.len() {
+        bail!("delta base size mismatch: expected {}, got {}", base_size, base.len());
+    }
+    let result_size = read_varint(delta, &mut pos)?;
+
+    let mut result = Vec::with_capacity(result_size as usize);
+    while pos < delta.len() {
+        let opcode = delta[pos];
+        pos += 1;
+
+        if opcode & 0x80 != 0 {
+            let mut offset: u32 = 0;
+            for i in 0..4 {
+                if opcode & (1 << i) != 0 {
+                    offset |= (delta[pos] as u32) << (8 * i);
+                    pos += 1;
+                }
+            }
+            let mut size: u32 = 0;
+            for i in 0..3 {
+                if opcode & (1 << (4 + i)) != 0 {
+                    size |= (delta[pos] as u32) << (8 * i);
+                    pos += 1;
+                }
+            }
+            if size == 0 {
+                size = 0x10000;
+            }
+            let (offset, size) = (offset as usize, size as usize);
+            result.extend_from_slice(
+                base.get(offset..offset + size)
+                    .with_context(|| "delta copy instruction out of bounds")?,
+            );
+        } else {
+            let size = opcode as usize;
+            result.extend_from_slice(
+                delta
+                    .get(pos..pos + size)
+                    .with_context(|| "delta insert instruction out of bounds")?,
+            );
+            pos += size;
+        }
+    }
+
+    if result.len() != result_size as usize {
+        bail!(
+            "delta result size mismatch: expected {}, got {}",
+            result_size,
+            result.len()
+        );
+    }
+    Ok(result)
+}
+
+// reads the raw "<type> <size>\0<content>" representation of an already
+// stored object, as opposed to `parse_object_hash` which decodes it into an
+// `ObjectHashTypes`. used to resolve ref-deltas against objects we already
+// have rather than ones still being unpacked.
+fn read_raw_object(hash: &str) -> Result<(String, Vec<u8>)> {
+    let (dir_path, object_path) = get_path_from_hash(hash);
+    let object_path = get_object_path(&dir_path, &object_path)?;
+    let file = File::open(&object_path)?;
+    let mut buffer = Vec::new();
+    flate2::read::ZlibDecoder::new(file).read_to_end(&mut buffer)?;
+
+    let nul = buffer
+        .iter()
+        .position(|&b| b == b'\0')
+        .with_context(|| "malformed object: missing metadata terminator")?;
+    let meta_data = std::str::from_utf8(&buffer[..nul])?;
+    let (content_type, _) = parse_file_metadata(meta_data)?;
+    Ok((content_type.to_string(), buffer[nul + 1..].to_vec()))
+}
+
+// unpacks every object in a PACK stream and writes it to the object
+// database via the existing `write_object` path, returning the hashes of
+// the objects in pack order.
+fn unpack_pack(pack: &[u8]) -> Result<Vec<Oid>> {
+    if pack.get(..4) != Some(b"PACK") {
+        bail!("not a pack file: missing PACK signature");
+    }
+    let version = u32::from_be_bytes(pack[4..8].try_into()?);
+    if version != 2 {
+        bail!("unsupported pack version: {}", version);
+    }
+    let object_count = u32::from_be_bytes(pack[8..12].try_into()?) as usize;
+
+    if pack.len() < 20 {
+        bail!("truncated pack: missing trailing checksum");
+    }
+    let mut hasher = Sha1::new();
+    hasher.update(&pack[..pack.len() - 20]);
+    let computed: [u8; 20] = hasher.finalize().into();
+    if computed != pack[pack.len() - 20..] {
+        bail!("pack checksum mismatch");
+    }
+
+    // resolved objects indexed both by their starting offset in the pack
+    // (for ofs-delta bases) and, once written, by hash (for ref-delta bases
+    // that are themselves in this pack).
+    let mut by_offset: HashMap<usize, (String, Vec<u8>)> = HashMap::new();
+    let mut order = Vec::with_capacity(object_count);
+
+    let mut pos = 12;
+    for _ in 0..object_count {
+        let start = pos;
+        let (obj_type, size) = read_pack_object_header(pack, &mut pos)?;
+
+        let (content_type, content) = match obj_type {
+            PACK_OBJ_COMMIT | PACK_OBJ_TREE | PACK_OBJ_BLOB | PACK_OBJ_TAG => {
+                let content = inflate_one(pack, &mut pos, size)?;
+                (pack_obj_type_name(obj_type)?.to_string(), content)
+            }
+            PACK_OBJ_OFS_DELTA => {
+                let base_offset = read_ofs_delta_offset(pack, &mut pos)?;
+                let base_start = start
+                    .checked_sub(base_offset as usize)
+                    .with_context(|| "ofs-delta base offset out of range")?;
+                let delta = inflate_one(pack, &mut pos, size)?;
+                let (base_type, base_content) = by_offset
+                    .get(&base_start)
+                    .with_context(|| "ofs-delta base not yet resolved")?;
+                (base_type.clone(), apply_delta(base_content, &delta)?)
+            }
+            PACK_OBJ_REF_DELTA => {
+                let base_hash = pack[pos..pos + NODE_HASH_BYTES_LENGTH]
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<String>();
+                pos += NODE_HASH_BYTES_LENGTH;
+                let delta = inflate_one(pack, &mut pos, size)?;
+                let (base_type, base_content) = read_raw_object(&base_hash)?;
+                (base_type, apply_delta(&base_content, &delta)?)
+            }
+            other => bail!("unsupported pack object type: {}", other),
+        };
+
+        let hash = write_object(
+            format!("{} {}\0", content_type, content.len()).as_bytes(),
+            &content,
+        )?;
+        by_offset.insert(start, (content_type, content));
+        order.push(hash);
+    }
+
+    Ok(order)
+}
+
+// --- unified diff ----------------------------------------------------------
+
+const DIFF_CONTEXT: usize = 3;
+
+enum LineOp {
+    Equal(String),
+    Delete(String),
+    Insert(String),
+}
+
+// computes the shortest edit script between two line vectors using Myers'
+// diff algorithm: `v[k]` tracks the furthest-reaching x on diagonal `k` for
+// the current edit distance `d`, and each step first takes whichever of
+// "down" (`v[k-1]`) or "right" (`v[k+1]`) reaches further, then follows the
+// free "snake" of matching lines before recording the new frontier.
+fn myers_edit_script(old: &[String], new: &[String]) -> Vec<LineOp> {
+    let (n, m) = (old.len() as i64, new.len() as i64);
+    let max = n + m;
+    if max == 0 {
+        return Vec::new();
+    }
+    let offset = max as usize;
+    let mut v = vec![0i64; 2 * max as usize + 1];
+    let mut trace = Vec::new();
+    let mut found_at = max;
+
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let idx = (k + offset as i64) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                found_at = d;
+                break 'search;
+            }
+        }
+    }
+
+    // backtrack through the recorded frontiers to recover the move taken at
+    // every edit distance, one line at a time, then reverse it into order.
+    let (mut x, mut y) = (n, m);
+    let mut moves = Vec::new();
+    for d in (0..=found_at).rev() {
+        let v = &trace[d as usize];
+        let k = x - y;
+        let idx = (k + offset as i64) as usize;
+        let prev_k = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_idx = (prev_k + offset as i64) as usize;
+        let prev_x = v[prev_idx];
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            moves.push((x - 1, y - 1, x, y));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            moves.push((prev_x, prev_y, x, y));
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    moves.reverse();
+
+    moves
+        .into_iter()
+        .map(|(from_x, from_y, to_x, to_y)| {
+            if to_x == from_x + 1 && to_y == from_y + 1 {
+                LineOp::Equal(old[from_x as usize].clone())
+            } else if to_x == from_x + 1 {
+                LineOp::Delete(old[from_x as usize].clone())
+            } else {
+                LineOp::Insert(new[from_y as usize].clone())
+            }
+        })
+        .collect()
+}
+
+// groups the edit script into `@@ -a,b +c,d @@` hunks, merging changes that
+// are within `2 * DIFF_CONTEXT` lines of each other, and renders each hunk
+// with three lines of surrounding context.
+fn format_unified_hunks(ops: &[LineOp]) -> String {
+    let changed_indices: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, LineOp::Equal(_)))
+        .map(|(i, _)| i)
+        .collect();
+
+    if changed_indices.is_empty() {
+        return String::new();
+    }
+
+    let mut clusters = Vec::new();
+    let (mut start, mut end) = (changed_indices[0], changed_indices[0]);
+    for &idx in &changed_indices[1..] {
+        if idx - end <= 2 * DIFF_CONTEXT {
+            end = idx;
+        } else {
+            clusters.push((start, end));
+            start = idx;
+            end = idx;
+        }
+    }
+    clusters.push((start, end));
+
+    let (mut old_line, mut new_line) = (0usize, 0usize);
+    let (mut old_nums, mut new_nums) = (Vec::with_capacity(ops.len()), Vec::with_capacity(ops.len()));
+    for op in ops {
+        match op {
+            LineOp::Equal(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+            LineOp::Delete(_) => old_line += 1,
+            LineOp::Insert(_) => new_line += 1,
+        }
+        old_nums.push(old_line);
+        new_nums.push(new_line);
+    }
+
+    let mut output = String::new();
+    for (start, end) in clusters {
+        let hunk_start = start.saturating_sub(DIFF_CONTEXT);
+        let hunk_end = (end + DIFF_CONTEXT + 1).min(ops.len());
+
+        let (old_before, new_before) = if hunk_start == 0 {
+            (0, 0)
+        } else {
+            (old_nums[hunk_start - 1], new_nums[hunk_start - 1])
+        };
+
+        let old_count = old_nums[hunk_end - 1] - old_before;
+        let new_count = new_nums[hunk_end - 1] - new_before;
+        let old_start = if old_count == 0 { 0 } else { old_before + 1 };
+        let new_start = if new_count == 0 { 0 } else { new_before + 1 };
+
+        let _ = write(
+            &mut output,
+            format_args!(
+                "@@ -{},{} +{},{} @@\n",
+                old_start, old_count, new_start, new_count
+            ),
+        );
+
+        for op in &ops[hunk_start..hunk_end] {
+            match op {
+                LineOp::Equal(line) => output.push_str(&format!(" {}\n", line)),
+                LineOp::Delete(line) => output.push_str(&format!("-{}\n", line)),
+                LineOp::Insert(line) => output.push_str(&format!("+{}\n", line)),
+            }
+        }
+    }
+    output
+}
+
+fn diff_lines_text(old_content: &str, new_content: &str) -> String {
+    let old_lines: Vec<String> = old_content.lines().map(|l| l.to_string()).collect();
+    let new_lines: Vec<String> = new_content.lines().map(|l| l.to_string()).collect();
+    format_unified_hunks(&myers_edit_script(&old_lines, &new_lines))
+}
+
+fn blob_bytes(hash: &str) -> Result<Vec<u8>> {
+    match parse_object_hash(hash)? {
+        ObjectHashTypes::Blob(content) => Ok(content),
+        _ => bail!("{} is not a blob", hash),
+    }
+}
+
+// lossily decodes a blob for line-based diffing; binary blobs will render
+// with replacement characters rather than failing, since diffing is
+// inherently a text operation.
+fn blob_text(hash: &str) -> Result<String> {
+    Ok(String::from_utf8_lossy(&blob_bytes(hash)?).into_owned())
+}
+
+fn print_blob_diff(old_hash: Option<&str>, new_hash: Option<&str>, path: &str) -> Result<()> {
+    let old_content = old_hash.map(blob_text).transpose()?.unwrap_or_default();
+    let new_content = new_hash.map(blob_text).transpose()?.unwrap_or_default();
+
+    let hunks = diff_lines_text(&old_content, &new_content);
+    if hunks.is_empty() {
+        return Ok(());
+    }
+
+    println!("diff --got a/{0} b/{0}", path);
+    println!(
+        "--- {}",
+        old_hash
+            .map(|_| format!("a/{}", path))
+            .unwrap_or_else(|| "/dev/null".to_string())
+    );
+    println!(
+        "+++ {}",
+        new_hash
+            .map(|_| format!("b/{}", path))
+            .unwrap_or_else(|| "/dev/null".to_string())
+    );
+    print!("{}", hunks);
+    Ok(())
+}
+
+// prints every file under `tree_hash` as either wholly added or wholly
+// removed, used when a tree-diff finds a subtree that only exists on one
+// side.
+fn print_whole_tree_diff(tree_hash: &str, prefix: &str, added: bool) -> Result<()> {
+    let nodes = match parse_object_hash(tree_hash)? {
+        ObjectHashTypes::Tree(nodes) => nodes,
+        _ => bail!("{} is not a tree", tree_hash),
+    };
+
+    for node in nodes {
+        let path = format!("{}{}", prefix, node.name);
+        if node.mode == 40000 {
+            print_whole_tree_diff(&node.hash.to_string(), &format!("{}/", path), added)?;
+        } else if added {
+            print_blob_diff(None, Some(&node.hash.to_string()), &path)?;
+        } else {
+            print_blob_diff(Some(&node.hash.to_string()), None, &path)?;
+        }
+    }
+    Ok(())
+}
+
+// walks two sorted `TreeNode` lists in parallel by name, reporting added,
+// removed, and modified entries and recursing into subtrees that exist on
+// both sides.
+fn diff_trees(old_tree: &str, new_tree: &str, prefix: &str) -> Result<()> {
+    let old_nodes = match parse_object_hash(old_tree)? {
+        ObjectHashTypes::Tree(nodes) => nodes,
+        _ => bail!("{} is not a tree", old_tree),
+    };
+    let new_nodes = match parse_object_hash(new_tree)? {
+        ObjectHashTypes::Tree(nodes) => nodes,
+        _ => bail!("{} is not a tree", new_tree),
+    };
+
+    let (mut i, mut j) = (0, 0);
+    while i < old_nodes.len() || j < new_nodes.len() {
+        match (old_nodes.get(i), new_nodes.get(j)) {
+            (Some(o), Some(n)) if o.name == n.name => {
+                if o.hash != n.hash {
+                    let path = format!("{}{}", prefix, o.name);
+                    if o.mode == 40000 && n.mode == 40000 {
+                        diff_trees(&o.hash.to_string(), &n.hash.to_string(), &format!("{}/", path))?;
+                    } else if o.mode == 40000 {
+                        print_whole_tree_diff(&o.hash.to_string(), &format!("{}/", path), false)?;
+                        print_blob_diff(None, Some(&n.hash.to_string()), &path)?;
+                    } else if n.mode == 40000 {
+                        print_blob_diff(Some(&o.hash.to_string()), None, &path)?;
+                        print_whole_tree_diff(&n.hash.to_string(), &format!("{}/", path), true)?;
+                    } else {
+                        print_blob_diff(Some(&o.hash.to_string()), Some(&n.hash.to_string()), &path)?;
+                    }
+                }
+                i += 1;
+                j += 1;
+            }
+            (Some(o), Some(n)) if o.name < n.name => {
+                let path = format!("{}{}", prefix, o.name);
+                if o.mode == 40000 {
+                    print_whole_tree_diff(&o.hash.to_string(), &format!("{}/", path), false)?;
+                } else {
+                    print_blob_diff(Some(&o.hash.to_string()), None, &path)?;
+                }
+                i += 1;
+            }
+            (Some(_), Some(n)) => {
+                let path = format!("{}{}", prefix, n.name);
+                if n.mode == 40000 {
+                    print_whole_tree_diff(&n.hash.to_string(), &format!("{}/", path), true)?;
+                } else {
+                    print_blob_diff(None, Some(&n.hash.to_string()), &path)?;
+                }
+                j += 1;
+            }
+            (Some(o), None) => {
+                let path = format!("{}{}", prefix, o.name);
+                if o.mode == 40000 {
+                    print_whole_tree_diff(&o.hash.to_string(), &format!("{}/", path), false)?;
+                } else {
+                    print_blob_diff(Some(&o.hash.to_string()), None, &path)?;
+                }
+                i += 1;
+            }
+            (None, Some(n)) => {
+                let path = format!("{}{}", prefix, n.name);
+                if n.mode == 40000 {
+                    print_whole_tree_diff(&n.hash.to_string(), &format!("{}/", path), true)?;
+                } else {
+                    print_blob_diff(None, Some(&n.hash.to_string()), &path)?;
+                }
+                j += 1;
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+    Ok(())
+}
+
+fn diff_objects(old: &str, new: &str) -> Result<()> {
+    match (parse_object_hash(old)?, parse_object_hash(new)?) {
+        (ObjectHashTypes::Blob(_), ObjectHashTypes::Blob(_)) => print_blob_diff(Some(old), Some(new), old),
+        (ObjectHashTypes::Tree(_), ObjectHashTypes::Tree(_)) => diff_trees(old, new, ""),
+        _ => bail!("diff only supports comparing two blobs or two trees"),
+    }
+}
+
+// --- tar archive export ------------------------------------------------
+
+// recursively appends every entry of `tree_hash` under `prefix` to the tar
+// stream, translating git modes to tar entry kinds: `100755` keeps the
+// executable bit, `120000` becomes a symlink whose target is the blob
+// content, and `160000` submodule links are skipped since we have no
+// submodule contents to materialize.
+fn archive_add_tree<W: Write>(builder: &mut tar::Builder<W>, tree_hash: &str, prefix: &str) -> Result<()> {
+    let nodes = match parse_object_hash(tree_hash)? {
+        ObjectHashTypes::Tree(nodes) => nodes,
+        _ => bail!("{} is not a tree", tree_hash),
+    };
+
+    for node in nodes {
+        let path = format!("{}{}", prefix, node.name);
+        match node.mode {
+            40000 => archive_add_tree(builder, &node.hash.to_string(), &format!("{}/", path))?,
+            160000 => continue,
+            120000 => {
+                let target = blob_text(&node.hash.to_string())?;
+                let mut header = tar::Header::new_gnu();
+                header.set_entry_type(tar::EntryType::Symlink);
+                header.set_size(0);
+                header.set_mode(0o120755);
+                header.set_cksum();
+                builder.append_link(&mut header, &path, &target)?;
+            }
+            mode => {
+                let content = blob_bytes(&node.hash.to_string())?;
+                let mut header = tar::Header::new_gnu();
+                header.set_size(content.len() as u64);
+                header.set_mode(if mode == 100755 { 0o755 } else { 0o644 });
+                header.set_cksum();
+                builder.append_data(&mut header, &path, content.as_slice())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn archive_add_root<W: Write>(builder: &mut tar::Builder<W>, hash: &str) -> Result<()> {
+    let tree_hash = match parse_object_hash(hash)? {
+        ObjectHashTypes::Commit(commit) => commit.tree_sha,
+        ObjectHashTypes::Tree(_) => hash.to_string(),
+        _ => bail!("{} is not a tree or commit", hash),
+    };
+    archive_add_tree(builder, &tree_hash, "")
+}
+
+fn write_archive(root_hash: &str, output: Option<String>) -> Result<()> {
+    let gzip = output
+        .as_deref()
+        .map(|path| path.ends_with(".gz") || path.ends_with(".tgz"))
+        .unwrap_or(false);
+
+    let writer: Box<dyn Write> = match &output {
+        Some(path) => Box::new(
+            File::create(path).with_context(|| format!("failed to create {}", path))?,
+        ),
+        None => Box::new(std::io::stdout()),
+    };
+
+    if gzip {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(writer, Compression::default()));
+        archive_add_root(&mut builder, root_hash)?;
+        builder.into_inner()?.finish()?;
+    } else {
+        let mut builder = tar::Builder::new(writer);
+        archive_add_root(&mut builder, root_hash)?;
+        builder.into_inner()?;
+    }
+    Ok(())
+}
+
+/// Resolve the commit SHA that `.git/HEAD` currently points at, following a
+/// single `ref: <path>` indirection.
+fn resolve_head() -> Result<String> {
+    let head = fs::read_to_string(".git/HEAD").with_context(|| "failed to read .git/HEAD")?;
+    let head = head.trim();
+    match head.strip_prefix("ref: ") {
+        Some(ref_path) => {
+            let full_path = format!(".git/{}", ref_path);
+            fs::read_to_string(&full_path)
+                .with_context(|| format!("failed to read {}", full_path))
+                .map(|sha| sha.trim().to_string())
+        }
+        None => Ok(head.to_string()),
+    }
+}
+
+/// Print a single commit in `git log` format: `commit <sha>`, `Author:`,
+/// `Date:`, a blank line, then the indented message.
+fn print_log_entry(sha: &str, commit: &CommitContent) {
+    println!("commit {}", sha);
+    // `author_email`/`committer_email` are stored without the `<>` that
+    // appear in the on-disk commit format (parse_object_hash strips them),
+    // so it's safe to add them back here without double-wrapping.
+    println!("Author: {} <{}>", commit.author_name, commit.author_email);
+    println!("Date:   {}", commit.author_timestamp.format("%a %b %e %T %Y %z"));
+    println!();
+    for line in commit.message.lines() {
+        println!("    {}", line);
+    }
+    println!();
+}
+
+/// Walk the ancestry of `start_sha` and print each commit in `git log`
+/// format, breadth-first, so merge histories with shared ancestors aren't
+/// re-emitted.
+fn print_log(start_sha: &str) -> Result<()> {
+    let mut cache: HashMap<String, CommitContent> = HashMap::new();
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut queue: VecDeque<String> = VecDeque::new();
+    queue.push_back(start_sha.to_string());
+    visited.insert(start_sha.to_string());
+
+    while let Some(sha) = queue.pop_front() {
+        if !cache.contains_key(&sha) {
+            let commit = match parse_object_hash(&sha)? {
+                ObjectHashTypes::Commit(commit) => commit,
+                _ => bail!("{} is not a commit", sha),
+            };
+            cache.insert(sha.clone(), commit);
+        }
+        let commit = cache.get(&sha).expect("just inserted");
+        print_log_entry(&sha, commit);
+
+        for parent_sha in &commit.parent_shas {
+            if visited.insert(parent_sha.clone()) {
+                queue.push_back(parent_sha.clone());
+            }
+        }
+    }
+
+    Ok(())
 }
 
 fn main() -> Result<()> {
@@ -489,18 +1593,13 @@ fn main() -> Result<()> {
             pretty_print,
             exist,
         } => match (pretty_print, exist) {
-            (None, Some(hash)) => {
-                let (dir_path, object_path) = get_path_from_hash(&hash);
-                let object_hash = get_object_path(&dir_path, &object_path);
-                match object_hash {
-                    Ok(full_object_hash) => {
-                        println!("object hash exist, with path: {}", full_object_hash)
-                    }
-                    Err(err) => println!("{}", err),
-                }
-            }
+            (None, Some(hash)) => match resolve_oid(&hash) {
+                Ok(oid) => println!("object hash exist, with path: {}", object_path_for(&oid)),
+                Err(err) => println!("{}", err),
+            },
             (Some(hash), None) => {
-                let object_hash = parse_object_hash(&hash)?;
+                let oid = resolve_oid(&hash)?;
+                let object_hash = parse_object_hash(&oid.to_string())?;
                 print!("{}", object_hash);
             }
             (_, _) => {
@@ -508,14 +1607,13 @@ fn main() -> Result<()> {
             }
         },
         Commands::HashObject { write } => {
-            let content =
-                String::from_utf8(fs::read(&write).context(format!("failed reading {}", &write))?)
-                    .context("failed parsing to string")?;
+            let content = fs::read(&write).context(format!("failed reading {}", &write))?;
             let hash_object = write_object_hash(ObjectHashTypes::Blob(content))?;
             println!("written object hash: {}", hash_object);
         }
         Commands::LsTree { hash } => {
-            let object_hash = parse_object_hash(&hash)?;
+            let oid = resolve_oid(&hash)?;
+            let object_hash = parse_object_hash(&oid.to_string())?;
             match object_hash {
                 ObjectHashTypes::Tree(_) => println!("{}", object_hash),
                 _ => println!("fatal: not a tree object"),
@@ -533,28 +1631,122 @@ fn main() -> Result<()> {
             parent_sha,
             message,
         } => {
-            let (author_name, author_email, committer, committer_email) = (
-                get_commit_author_name(),
-                get_commit_author_email(),
-                get_commit_comitter_name(),
-                get_commit_comitter_email(),
-            );
+            let tree_sha = resolve_oid(&tree_sha)?.to_string();
+            let parent_shas = parent_sha
+                .map(|sha| resolve_oid(&sha))
+                .transpose()?
+                .map(|oid| oid.to_string())
+                .into_iter()
+                .collect();
+
+            let config = load_git_config();
+            let (author_name, author_email) =
+                resolve_identity(&config, "GIT_AUTHOR_NAME", "GIT_AUTHOR_EMAIL")?;
+            let (committer, committer_email) =
+                resolve_identity(&config, "GIT_COMMITTER_NAME", "GIT_COMMITTER_EMAIL")?;
+            let author_timestamp = resolve_timestamp("GIT_AUTHOR_DATE")?;
+            let committer_timestamp = resolve_timestamp("GIT_COMMITTER_DATE")?;
 
-            let timestamp = Utc::now().fixed_offset();
             let commit_content = CommitContent {
                 tree_sha,
-                parent_sha,
+                parent_shas,
                 author_name,
                 author_email,
                 committer,
                 committer_email,
                 message,
-                timestamp,
+                author_timestamp,
+                committer_timestamp,
             };
 
             let object_hash = write_object_hash(ObjectHashTypes::Commit(commit_content))?;
             println!("written object hash: {}", object_hash);
         }
+        Commands::Clone { url, directory } => {
+            let directory = directory.unwrap_or_else(|| {
+                url.trim_end_matches('/')
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or("repository")
+                    .trim_end_matches(".git")
+                    .to_string()
+            });
+            fs::create_dir_all(format!("{}/{}", directory, GIT_OBJECT_PATH))
+                .context("failed to initialize .git/objects directory")?;
+            fs::create_dir_all(format!("{}/.git/refs/heads", directory))
+                .context("failed to initialize .git/refs directory")?;
+            env::set_current_dir(&directory)?;
+
+            let (refs, pack) = fetch_refs_and_pack(&url)?;
+            unpack_pack(&pack)?;
+            write_fetched_refs(&refs)?;
+            println!("cloned {} into {}", url, directory);
+        }
+        Commands::Fetch { url } => {
+            let (refs, pack) = fetch_refs_and_pack(&url)?;
+            unpack_pack(&pack)?;
+            write_fetched_refs(&refs)?;
+            println!("fetched {} ref(s) from {}", refs.len(), url);
+        }
+        Commands::Diff { old, new } => {
+            diff_objects(&old, &new)?;
+        }
+        Commands::Archive {
+            tree_or_commit,
+            output,
+        } => {
+            write_archive(&tree_or_commit, output)?;
+        }
+        Commands::Log { commit_sha } => {
+            let start_sha = match commit_sha {
+                Some(sha) => resolve_oid(&sha)?.to_string(),
+                None => resolve_head()?,
+            };
+            print_log(&start_sha)?;
+        }
+    }
+    Ok(())
+}
+
+fn fetch_refs_and_pack(url: &str) -> Result<(RemoteRefs, Vec<u8>)> {
+    let client = reqwest::blocking::Client::new();
+    discover_refs(&client, url)?;
+    let refs = ls_refs(&client, url)?;
+    let wants: Vec<String> = refs.iter().map(|(_, sha)| sha.clone()).collect();
+    if wants.is_empty() {
+        bail!("remote {} advertised no refs", url);
+    }
+    let pack = fetch_pack(&client, url, &wants)?;
+    Ok((refs, pack))
+}
+
+fn write_fetched_refs(refs: &RemoteRefs) -> Result<()> {
+    let head_target = refs
+        .iter()
+        .find(|(name, _)| name == "HEAD")
+        .map(|(_, sha)| sha.clone());
+
+    for (name, sha) in refs {
+        if name == "HEAD" {
+            continue;
+        }
+        if let Some(path) = name.strip_prefix("refs/") {
+            let full_path = format!(".git/refs/{}", path);
+            if let Some(parent) = std::path::Path::new(&full_path).parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&full_path, format!("{}\n", sha))?;
+        }
+    }
+
+    if let Some(head_sha) = head_target {
+        let branch = refs
+            .iter()
+            .find(|(name, sha)| name.starts_with("refs/heads/") && *sha == head_sha)
+            .map(|(name, _)| name.clone())
+            .unwrap_or_else(|| "refs/heads/main".to_string());
+        fs::write(".git/HEAD", format!("ref: {}\n", branch))?;
     }
+
     Ok(())
 }